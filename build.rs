@@ -16,6 +16,7 @@ fn main() -> io::Result<()> {
         "sets",
         "generate",
         &mut sets::SplitByCount::new(2),
+        None,
     )?;
 
     Ok(())
@@ -1,354 +1,79 @@
-use actix_service::{Service, ServiceFactory};
-use actix_web::{
-    dev::{AppService, HttpServiceFactory, ResourceDef, ServiceRequest, ServiceResponse},
-    error::Error,
-    http::{header, Method, StatusCode},
-    HttpMessage, HttpRequest, HttpResponse, ResponseError,
-};
-use derive_more::{Display, Error};
-use futures::future::{ok, FutureExt, LocalBoxFuture, Ready};
 use path_slash::PathExt;
 use std::{
-    collections::HashMap,
     env,
     fs::{self, File, Metadata},
     io::{self, Write},
-    ops::Deref,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
-    rc::Rc,
-    task::{Context, Poll},
+    process::Command,
     time::SystemTime,
 };
 
+/// A `Content-Encoding` produced by build-time precompression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Brotli,
+}
+
+impl Encoding {
+    /// The `Content-Encoding` / `Accept-Encoding` token for this encoding.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Brotli => "br",
+        }
+    }
+}
+
 /// Static files resource.
 pub struct Resource {
     pub data: &'static [u8],
     pub modified: u64,
     pub mime_type: &'static str,
+    /// Precomputed variants of `data`, embedded when built with
+    /// [`Compression::Gzip`], [`Compression::Brotli`] or
+    /// [`Compression::BrotliAndGzip`]; empty otherwise. Listed in preference
+    /// order (brotli before gzip), so a handler can pick the first entry
+    /// whose encoding the request's `Accept-Encoding` allows.
+    pub compressed: &'static [(Encoding, &'static [u8])],
+    /// Strong validator derived from `data` at build time (a truncated SHA-256
+    /// hex digest), used as the `ETag` so caches invalidate only when content
+    /// actually changes, rather than on every rebuild.
+    pub etag: &'static str,
 }
 
 #[inline]
-pub fn new_resource(data: &'static [u8], modified: u64, mime_type: &'static str) -> Resource {
+pub fn new_resource(
+    data: &'static [u8],
+    modified: u64,
+    mime_type: &'static str,
+    etag: &'static str,
+) -> Resource {
     Resource {
         data,
         modified,
         mime_type,
+        compressed: &[],
+        etag,
     }
 }
 
-/// Static resource files handling
-///
-/// `ResourceFiles` service must be registered with `App::service` method.
-///
-/// ```rust
-/// use std::collections::HashMap;
-///
-/// use actix_web::App;
-///
-/// fn main() {
-/// // serve root directory with default options:
-/// // - resolve index.html
-///     let files: HashMap<&'static str, actix_web_static_files::Resource> = HashMap::new();
-///     let app = App::new()
-///         .service(actix_web_static_files::ResourceFiles::new("/", files));
-/// // or subpath with additional option to not resolve index.html
-///     let files: HashMap<&'static str, actix_web_static_files::Resource> = HashMap::new();
-///     let app = App::new()
-///         .service(actix_web_static_files::ResourceFiles::new("/imgs", files)
-///             .do_not_resolve_defaults());
-/// }
-/// ```
-pub struct ResourceFiles {
-    not_resolve_defaults: bool,
-    not_found_resolves_to: Option<String>,
-    inner: Rc<ResourceFilesInner>,
-}
-
-pub struct ResourceFilesInner {
-    path: String,
-    files: HashMap<&'static str, Resource>,
-}
-
-const INDEX_HTML: &str = "index.html";
-
-impl ResourceFiles {
-    pub fn new(path: &str, files: HashMap<&'static str, Resource>) -> Self {
-        let inner = ResourceFilesInner {
-            path: path.into(),
-            files,
-        };
-        Self {
-            inner: Rc::new(inner),
-            not_resolve_defaults: false,
-            not_found_resolves_to: None,
-        }
-    }
-
-    /// By default trying to resolve '.../' to '.../index.html' if it exists.
-    /// Turn off this resolution by calling this function.
-    pub fn do_not_resolve_defaults(mut self) -> Self {
-        self.not_resolve_defaults = true;
-        self
-    }
-
-    /// Resolves not found references to this path.
-    ///
-    /// This can be useful for angular-like applications.
-    pub fn resolve_not_found_to<S: ToString>(mut self, path: S) -> Self {
-        self.not_found_resolves_to = Some(path.to_string());
-        self
-    }
-
-    /// Resolves not found references to root path.
-    ///
-    /// This can be useful for angular-like applications.
-    pub fn resolve_not_found_to_root(self) -> Self {
-        self.resolve_not_found_to(INDEX_HTML)
-    }
-}
-
-impl Deref for ResourceFiles {
-    type Target = ResourceFilesInner;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
-    }
-}
-
-impl HttpServiceFactory for ResourceFiles {
-    fn register(self, config: &mut AppService) {
-        let rdef = if config.is_root() {
-            ResourceDef::root_prefix(&self.path)
-        } else {
-            ResourceDef::prefix(&self.path)
-        };
-        config.register_service(rdef, None, self, None)
-    }
-}
-
-impl ServiceFactory for ResourceFiles {
-    type Config = ();
-    type Request = ServiceRequest;
-    type Response = ServiceResponse;
-    type Error = Error;
-    type Service = ResourceFilesService;
-    type InitError = ();
-    type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
-
-    fn new_service(&self, _: ()) -> Self::Future {
-        ok(ResourceFilesService {
-            resolve_defaults: !self.not_resolve_defaults,
-            not_found_resolves_to: self.not_found_resolves_to.clone(),
-            inner: self.inner.clone(),
-        })
-        .boxed_local()
-    }
-}
-
-pub struct ResourceFilesService {
-    resolve_defaults: bool,
-    not_found_resolves_to: Option<String>,
-    inner: Rc<ResourceFilesInner>,
-}
-
-impl Deref for ResourceFilesService {
-    type Target = ResourceFilesInner;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
-    }
-}
-
-impl<'a> Service for ResourceFilesService {
-    type Request = ServiceRequest;
-    type Response = ServiceResponse;
-    type Error = Error;
-    type Future = Ready<Result<Self::Response, Self::Error>>;
-
-    fn poll_ready(&mut self, _: &mut Context) -> Poll<Result<(), Self::Error>> {
-        Poll::Ready(Ok(()))
-    }
-
-    fn call(&mut self, req: ServiceRequest) -> Self::Future {
-        match *req.method() {
-            Method::HEAD | Method::GET => (),
-            _ => {
-                return ok(ServiceResponse::new(
-                    req.into_parts().0,
-                    HttpResponse::MethodNotAllowed()
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .header(header::ALLOW, "GET, HEAD")
-                        .body("This resource only supports GET and HEAD."),
-                ));
-            }
-        }
-
-        let req_path = req.match_info().path();
-
-        let mut item = self.files.get(req_path);
-
-        if item.is_none()
-            && self.resolve_defaults
-            && (req_path.is_empty() || req_path.ends_with("/"))
-        {
-            let index_req_path = req_path.to_string() + INDEX_HTML;
-            item = self.files.get(index_req_path.as_str());
-        }
-
-        let (req, response) = if item.is_some() {
-            let (req, _) = req.into_parts();
-            let response = respond_to(&req, item);
-            (req, response)
-        } else {
-            let real_path = match get_pathbuf(req_path) {
-                Ok(item) => item,
-                Err(e) => return ok(req.error_response(e)),
-            };
-
-            let (req, _) = req.into_parts();
-
-            let mut item = self.files.get(real_path.as_str());
-
-            if item.is_none() && self.not_found_resolves_to.is_some() {
-                let not_found_path = self.not_found_resolves_to.as_ref().unwrap();
-                item = self.files.get(not_found_path.as_str());
-            }
-
-            let response = respond_to(&req, item);
-            (req, response)
-        };
-
-        ok(ServiceResponse::new(req, response))
-    }
-}
-
-fn respond_to(req: &HttpRequest, item: Option<&Resource>) -> HttpResponse {
-    if let Some(file) = item {
-        let etag = Some(header::EntityTag::strong(format!(
-            "{:x}:{:x}",
-            file.data.len(),
-            file.modified
-        )));
-
-        let precondition_failed = !any_match(etag.as_ref(), req);
-
-        let not_modified = !none_match(etag.as_ref(), req);
-
-        let mut resp = HttpResponse::build(StatusCode::OK);
-        resp.set_header(header::CONTENT_TYPE, file.mime_type);
-
-        if let Some(etag) = etag {
-            resp.set(header::ETag(etag));
-        }
-
-        if precondition_failed {
-            return resp.status(StatusCode::PRECONDITION_FAILED).finish();
-        } else if not_modified {
-            return resp.status(StatusCode::NOT_MODIFIED).finish();
-        }
-
-        resp.body(file.data)
-    } else {
-        HttpResponse::NotFound().body("Not found")
-    }
-}
-
-/// Returns true if `req` has no `If-Match` header or one which matches `etag`.
-fn any_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
-    match req.get_header::<header::IfMatch>() {
-        None | Some(header::IfMatch::Any) => true,
-        Some(header::IfMatch::Items(ref items)) => {
-            if let Some(some_etag) = etag {
-                for item in items {
-                    if item.strong_eq(some_etag) {
-                        return true;
-                    }
-                }
-            }
-            false
-        }
-    }
-}
-
-/// Returns true if `req` doesn't have an `If-None-Match` header matching `req`.
-fn none_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
-    match req.get_header::<header::IfNoneMatch>() {
-        Some(header::IfNoneMatch::Any) => false,
-        Some(header::IfNoneMatch::Items(ref items)) => {
-            if let Some(some_etag) = etag {
-                for item in items {
-                    if item.weak_eq(some_etag) {
-                        return false;
-                    }
-                }
-            }
-            true
-        }
-        None => true,
-    }
-}
-
-#[derive(Debug, PartialEq, Display, Error)]
-pub enum UriSegmentError {
-    /// The segment started with the wrapped invalid character.
-    #[display(fmt = "The segment started with the wrapped invalid character")]
-    BadStart(#[error(not(source))] char),
-
-    /// The segment contained the wrapped invalid character.
-    #[display(fmt = "The segment contained the wrapped invalid character")]
-    BadChar(#[error(not(source))] char),
-
-    /// The segment ended with the wrapped invalid character.
-    #[display(fmt = "The segment ended with the wrapped invalid character")]
-    BadEnd(#[error(not(source))] char),
-}
-
-#[cfg(test)]
-mod tests_error_impl {
-    use super::*;
-
-    fn assert_send_and_sync<T: Send + Sync + 'static>() {}
-
-    #[test]
-    fn test_error_impl() {
-        // ensure backwards compatibility when migrating away from failure
-        assert_send_and_sync::<UriSegmentError>();
-    }
-}
-
-/// Return `BadRequest` for `UriSegmentError`
-impl ResponseError for UriSegmentError {
-    fn error_response(&self) -> HttpResponse {
-        HttpResponse::new(StatusCode::BAD_REQUEST)
-    }
-}
-
-fn get_pathbuf(path: &str) -> Result<String, UriSegmentError> {
-    let mut buf = Vec::new();
-    for segment in path.split('/') {
-        if segment == ".." {
-            buf.pop();
-        } else if segment.starts_with('.') {
-            return Err(UriSegmentError::BadStart('.'));
-        } else if segment.starts_with('*') {
-            return Err(UriSegmentError::BadStart('*'));
-        } else if segment.ends_with(':') {
-            return Err(UriSegmentError::BadEnd(':'));
-        } else if segment.ends_with('>') {
-            return Err(UriSegmentError::BadEnd('>'));
-        } else if segment.ends_with('<') {
-            return Err(UriSegmentError::BadEnd('<'));
-        } else if segment.is_empty() {
-            continue;
-        } else if cfg!(windows) && segment.contains('\\') {
-            return Err(UriSegmentError::BadChar('\\'));
-        } else {
-            buf.push(segment)
-        }
+/// Like [`new_resource`], but also embeds build-time precompressed variants.
+#[inline]
+pub fn new_resource_with_compression(
+    data: &'static [u8],
+    modified: u64,
+    mime_type: &'static str,
+    compressed: &'static [(Encoding, &'static [u8])],
+    etag: &'static str,
+) -> Resource {
+    Resource {
+        data,
+        modified,
+        mime_type,
+        compressed,
+        etag,
     }
-
-    Ok(buf.join("/"))
 }
 
 fn collect_resources<P: AsRef<Path>>(
@@ -395,16 +120,46 @@ pub fn resource_dir<P: AsRef<Path>>(resource_dir: P) -> ResourceDir {
     }
 }
 
+/// Which build-time precompressed variants to embed alongside the identity bytes.
+///
+/// Opt-in via [`ResourceDir::with_compression`]; existing callers that never set
+/// this keep generating plain (uncompressed) resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Brotli,
+    BrotliAndGzip,
+}
+
+impl Compression {
+    fn gzip(self) -> bool {
+        matches!(self, Compression::Gzip | Compression::BrotliAndGzip)
+    }
+
+    fn brotli(self) -> bool {
+        matches!(self, Compression::Brotli | Compression::BrotliAndGzip)
+    }
+}
+
 #[derive(Default)]
 pub struct ResourceDir {
     resource_dir: PathBuf,
     filter: Option<fn(p: &Path) -> bool>,
     generated_filename: Option<PathBuf>,
     generated_fn: Option<String>,
+    compression: Compression,
+    npm_build: Option<NpmBuildConfig>,
+    spa_index: Option<String>,
 }
 
 impl ResourceDir {
     pub fn build(&self) -> io::Result<()> {
+        if let Some(npm_build) = &self.npm_build {
+            npm_build.execute()?;
+        }
+
         let generated_filename = self.generated_filename.clone().unwrap_or_else(|| {
             let out_dir = env::var("OUT_DIR").unwrap();
 
@@ -415,12 +170,31 @@ impl ResourceDir {
             .clone()
             .unwrap_or_else(|| "generate".into());
 
-        generate_resources(
-            &self.resource_dir,
-            self.filter,
-            &generated_filename,
-            &generated_fn,
-        )
+        if self.compression == Compression::None {
+            generate_resources(
+                &self.resource_dir,
+                self.filter,
+                &generated_filename,
+                &generated_fn,
+            )?;
+        } else {
+            generate_resources_compressed(
+                &self.resource_dir,
+                self.filter,
+                &generated_filename,
+                &generated_fn,
+                self.compression,
+            )?;
+        }
+
+        if let Some(spa_index) = &self.spa_index {
+            let mut f = fs::OpenOptions::new()
+                .append(true)
+                .open(&generated_filename)?;
+            generate_resolve_fn(&mut f, &generated_fn, spa_index)?;
+        }
+
+        Ok(())
     }
 
     pub fn with_filter(&mut self, filter: fn(p: &Path) -> bool) -> &mut Self {
@@ -437,6 +211,145 @@ impl ResourceDir {
         self.generated_fn = Some(generated_fn.into());
         self
     }
+
+    /// Additionally embeds gzip and/or brotli variants of each compressible file,
+    /// computed once at build time so serving them costs no runtime CPU.
+    pub fn with_compression(&mut self, compression: Compression) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Runs `npm_build` (or a configured equivalent, like `yarn`) in its working
+    /// directory before resources are scanned.
+    ///
+    /// Useful when `resource_dir` points at the output of a JS build, e.g. a
+    /// webpack/vite `dist/` directory that still needs to be produced:
+    ///
+    /// ```rust#ignore
+    /// use actix_web_static_files::{resource_dir, NpmBuildConfig};
+    ///
+    /// resource_dir("./web/dist")
+    ///     .with_npm_build(NpmBuildConfig::new("./web").run("build"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn with_npm_build(&mut self, npm_build: NpmBuildConfig) -> &mut Self {
+        self.npm_build = Some(npm_build);
+        self
+    }
+
+    /// Emits an additional `<generated_fn>_resolve` function alongside the
+    /// generated `<generated_fn>`, implementing client-side-router fallback:
+    /// a lookup that misses and doesn't look like a file request (no `.` in
+    /// the last path segment) falls through to `index_path` instead of `None`.
+    ///
+    /// Useful for Angular/React/Vue-style SPAs, where deep links like
+    /// `/users/42` don't exist as embedded resources and should resolve to
+    /// the app shell:
+    ///
+    /// ```rust#ignore
+    /// use actix_web_static_files::resource_dir;
+    ///
+    /// resource_dir("./web/dist")
+    ///     .with_spa_index("index.html")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn with_spa_index(&mut self, index_path: impl Into<String>) -> &mut Self {
+        self.spa_index = Some(index_path.into());
+        self
+    }
+}
+
+/// Commands to run in a working directory before [`ResourceDir::build`] scans
+/// resources, e.g. `npm install` followed by `npm run build`.
+///
+/// Created with [`NpmBuildConfig::new`], which seeds the install step, and
+/// installed onto a [`ResourceDir`] via [`ResourceDir::with_npm_build`]. Each
+/// command's stdout/stderr is captured and re-emitted as `cargo:warning=` lines
+/// so failures show up in the `cargo build` output, and a non-zero exit status
+/// fails the build, via the shared [`run_build_command`] helper.
+///
+/// This exists alongside [`NpmBuild`] rather than reusing it directly: `NpmBuild`
+/// runs each command eagerly as soon as `.install()`/`.run()` is chained, so it
+/// can stand alone as a `build.rs` script. `with_npm_build` instead needs to
+/// *queue* commands on a plain builder call and defer running them until
+/// [`ResourceDir::build`], alongside the rest of the resource-generation step.
+#[derive(Debug, Clone)]
+pub struct NpmBuildConfig {
+    dir: PathBuf,
+    package_manager: String,
+    commands: Vec<Vec<String>>,
+}
+
+impl NpmBuildConfig {
+    /// Runs `npm install` (or the configured package manager) in `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().into(),
+            package_manager: NPM_CMD.to_string(),
+            commands: vec![vec!["install".to_string()]],
+        }
+    }
+
+    /// Uses `package_manager` (e.g. `"yarn"`, `"pnpm"`) instead of `npm`.
+    pub fn package_manager(mut self, package_manager: impl Into<String>) -> Self {
+        self.package_manager = package_manager.into();
+        self
+    }
+
+    /// Runs `package_manager run <cmd>`, e.g. `npm run build`.
+    pub fn run(self, cmd: &str) -> Self {
+        self.command(["run", cmd])
+    }
+
+    /// Runs an arbitrary `package_manager` invocation with the given arguments.
+    pub fn command<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.commands.push(args.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn execute(&self) -> io::Result<()> {
+        for args in &self.commands {
+            run_build_command(&self.package_manager, args, &self.dir)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_npm_build_config {
+    use super::*;
+
+    #[test]
+    fn new_seeds_install_with_configured_package_manager() {
+        let config = NpmBuildConfig::new("./web");
+        assert_eq!(config.package_manager, NPM_CMD);
+        assert_eq!(config.commands, vec![vec!["install".to_string()]]);
+    }
+
+    #[test]
+    fn run_and_command_queue_in_order_without_executing() {
+        let config = NpmBuildConfig::new("./web")
+            .package_manager("yarn")
+            .run("build")
+            .command(["run", "lint"]);
+
+        assert_eq!(config.package_manager, "yarn");
+        assert_eq!(
+            config.commands,
+            vec![
+                vec!["install".to_string()],
+                vec!["run".to_string(), "build".to_string()],
+                vec!["run".to_string(), "lint".to_string()],
+            ]
+        );
+    }
 }
 
 const DEFAULT_VARIABLE_NAME: &str = "r";
@@ -518,6 +431,51 @@ pub fn generate_resources_mapping<P: AsRef<Path>, G: AsRef<Path>>(
     Ok(())
 }
 
+/// Per-file data shared by [`generate_resource_inserts`] and
+/// [`generate_resource_inserts_compressed`]: the file's key (its path relative
+/// to `project_dir`),
+/// embedded mtime, guessed mime type, and raw bytes.
+struct ResourceInsert {
+    abs_path: PathBuf,
+    key_path: String,
+    modified: u64,
+    mime_type: mime_guess::Mime,
+    data: Vec<u8>,
+}
+
+fn resource_insert<P: AsRef<Path>>(
+    path: &Path,
+    project_dir: &P,
+    metadata: &Metadata,
+) -> io::Result<ResourceInsert> {
+    let abs_path = path.canonicalize()?;
+    let key_path = path
+        .strip_prefix(project_dir)
+        .unwrap()
+        .to_slash()
+        .unwrap()
+        .into_owned();
+
+    let modified = if let Ok(Ok(modified)) = metadata
+        .modified()
+        .map(|x| x.duration_since(SystemTime::UNIX_EPOCH))
+    {
+        modified.as_secs()
+    } else {
+        0
+    };
+    let mime_type = mime_guess::MimeGuess::from_path(path).first_or_octet_stream();
+    let data = fs::read(path)?;
+
+    Ok(ResourceInsert {
+        abs_path,
+        key_path,
+        modified,
+        mime_type,
+        data,
+    })
+}
+
 fn generate_resource_inserts<P: AsRef<Path>, W: Write>(
     f: &mut W,
     project_dir: &P,
@@ -525,27 +483,237 @@ fn generate_resource_inserts<P: AsRef<Path>, W: Write>(
     resources: Vec<(PathBuf, Metadata)>,
 ) -> io::Result<()> {
     for (path, metadata) in resources {
-        let abs_path = path.canonicalize()?;
-        let key_path = path.strip_prefix(&project_dir).unwrap().to_slash().unwrap();
+        let ResourceInsert {
+            abs_path,
+            key_path,
+            modified,
+            mime_type,
+            data,
+        } = resource_insert(&path, project_dir, &metadata)?;
+        let etag = content_etag(&data);
+        writeln!(
+            f,
+            "{}.insert({:?},n(i!({:?}),{:?},{:?},{:?}));",
+            variable_name, &key_path, &abs_path, modified, &mime_type, &etag,
+        )?;
+    }
+    Ok(())
+}
 
-        let modified = if let Ok(Ok(modified)) = metadata
-            .modified()
-            .map(|x| x.duration_since(SystemTime::UNIX_EPOCH))
-        {
-            modified.as_secs()
+/// Like [`generate_resources`], but also computes and embeds precompressed
+/// variants of each compressible file per `compression`.
+fn generate_resources_compressed<P: AsRef<Path>, G: AsRef<Path>>(
+    project_dir: P,
+    filter: Option<fn(p: &Path) -> bool>,
+    generated_filename: G,
+    fn_name: &str,
+    compression: Compression,
+) -> io::Result<()> {
+    let resources = collect_resources(&project_dir, filter)?;
+
+    let mut f = File::create(&generated_filename).unwrap();
+
+    generate_function_header(&mut f, fn_name)?;
+    generate_uses_compressed(&mut f)?;
+
+    generate_variable_header(&mut f, DEFAULT_VARIABLE_NAME)?;
+    generate_resource_inserts_compressed(
+        &mut f,
+        &project_dir,
+        DEFAULT_VARIABLE_NAME,
+        resources,
+        compression,
+    )?;
+    generate_variable_return(&mut f, DEFAULT_VARIABLE_NAME)?;
+
+    generate_function_end(&mut f)?;
+
+    Ok(())
+}
+
+fn generate_uses_compressed<F: Write>(f: &mut F) -> io::Result<()> {
+    writeln!(
+        f,
+        "use ::actix_web_static_files::new_resource_with_compression as n;
+use ::actix_web_static_files::Encoding as E;
+use ::std::include_bytes as i;",
+    )
+}
+
+/// Minimum size, in bytes, below which precompressing a file is not worth the
+/// embedded binary bloat.
+const COMPRESSION_SIZE_THRESHOLD: u64 = 1024;
+
+/// Mime type prefixes/names that are already compressed (images, video, fonts) and
+/// therefore not worth recompressing.
+const INCOMPRESSIBLE_MIME_TYPES: &[&str] = &[
+    "image/", "video/", "audio/", "font/woff", "application/wasm", "application/zip",
+    "application/gzip",
+];
+
+fn is_compressible(mime_type: &mime_guess::Mime, size: u64) -> bool {
+    if size < COMPRESSION_SIZE_THRESHOLD {
+        return false;
+    }
+    let essence = mime_type.essence_str();
+    !INCOMPRESSIBLE_MIME_TYPES
+        .iter()
+        .any(|prefix| essence.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests_compression {
+    use super::*;
+
+    #[test]
+    fn compression_flags_select_expected_encodings() {
+        assert!(!Compression::None.gzip());
+        assert!(!Compression::None.brotli());
+        assert!(Compression::Gzip.gzip());
+        assert!(!Compression::Gzip.brotli());
+        assert!(Compression::Brotli.brotli());
+        assert!(!Compression::Brotli.gzip());
+        assert!(Compression::BrotliAndGzip.gzip());
+        assert!(Compression::BrotliAndGzip.brotli());
+    }
+
+    #[test]
+    fn is_compressible_rejects_small_files() {
+        let mime_type: mime_guess::Mime = "text/plain".parse().unwrap();
+        assert!(!is_compressible(&mime_type, COMPRESSION_SIZE_THRESHOLD - 1));
+        assert!(is_compressible(&mime_type, COMPRESSION_SIZE_THRESHOLD + 1));
+    }
+
+    #[test]
+    fn is_compressible_rejects_already_compressed_mime_types() {
+        let mime_type: mime_guess::Mime = "image/png".parse().unwrap();
+        assert!(!is_compressible(&mime_type, COMPRESSION_SIZE_THRESHOLD * 10));
+    }
+}
+
+fn compressed_sibling_path(out_dir: &Path, key_path: &str, suffix: &str) -> PathBuf {
+    let file_name = format!("{}.{}", key_path.replace('/', "_"), suffix);
+    out_dir.join("compressed").join(file_name)
+}
+
+/// Gzip-compresses `data` and, only if the result is actually smaller than
+/// `data`, writes it to `dest` and returns `true`; otherwise returns `false`.
+fn write_gzip_variant(data: &[u8], dest: &Path) -> io::Result<bool> {
+    use flate2::{write::GzEncoder, Compression as GzCompression};
+
+    fs::create_dir_all(dest.parent().unwrap())?;
+    let mut encoder = GzEncoder::new(Vec::new(), GzCompression::best());
+    encoder.write_all(data)?;
+    let compressed = encoder.finish()?;
+
+    if compressed.len() >= data.len() {
+        return Ok(false);
+    }
+
+    fs::write(dest, compressed)?;
+    Ok(true)
+}
+
+/// Brotli-compresses `data` and, only if the result is actually smaller than
+/// `data`, writes it to `dest` and returns `true`; otherwise returns `false`.
+fn write_brotli_variant(data: &[u8], dest: &Path) -> io::Result<bool> {
+    use std::io::Cursor;
+
+    fs::create_dir_all(dest.parent().unwrap())?;
+    let mut compressed = Vec::new();
+    let params = brotli::enc::BrotliEncoderParams::default();
+    brotli::BrotliCompress(&mut Cursor::new(data), &mut compressed, &params)?;
+
+    if compressed.len() >= data.len() {
+        return Ok(false);
+    }
+
+    fs::write(dest, compressed)?;
+    Ok(true)
+}
+
+fn generate_resource_inserts_compressed<P: AsRef<Path>, W: Write>(
+    f: &mut W,
+    project_dir: &P,
+    variable_name: &str,
+    resources: Vec<(PathBuf, Metadata)>,
+    compression: Compression,
+) -> io::Result<()> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    for (path, metadata) in resources {
+        let ResourceInsert {
+            abs_path,
+            key_path,
+            modified,
+            mime_type,
+            data,
+        } = resource_insert(&path, project_dir, &metadata)?;
+
+        let gzip_path = if compression.gzip() && is_compressible(&mime_type, metadata.len()) {
+            let dest = compressed_sibling_path(&out_dir, &key_path, "gz");
+            write_gzip_variant(&data, &dest)?.then(|| dest.canonicalize()).transpose()?
+        } else {
+            None
+        };
+
+        let br_path = if compression.brotli() && is_compressible(&mime_type, metadata.len()) {
+            let dest = compressed_sibling_path(&out_dir, &key_path, "br");
+            write_brotli_variant(&data, &dest)?.then(|| dest.canonicalize()).transpose()?
         } else {
-            0
+            None
         };
-        let mime_type = mime_guess::MimeGuess::from_path(&path).first_or_octet_stream();
+
+        // Brotli first: when both are embedded, a handler taking the first
+        // accepted entry prefers brotli over gzip.
+        let mut compressed_variants = Vec::new();
+        if let Some(p) = &br_path {
+            compressed_variants.push(format!("(E::Brotli,i!({:?}))", p));
+        }
+        if let Some(p) = &gzip_path {
+            compressed_variants.push(format!("(E::Gzip,i!({:?}))", p));
+        }
+        let compressed_expr = format!("&[{}]", compressed_variants.join(","));
+        let etag = content_etag(&data);
+
         writeln!(
             f,
-            "{}.insert({:?},n(i!({:?}),{:?},{:?}));",
-            variable_name, &key_path, &abs_path, modified, &mime_type,
+            "{}.insert({:?},n(i!({:?}),{:?},{:?},{},{:?}));",
+            variable_name, &key_path, &abs_path, modified, &mime_type, compressed_expr, &etag,
         )?;
     }
     Ok(())
 }
 
+/// Derives a strong, content-addressed `ETag` validator for `data`: a truncated
+/// (64-bit) hex-encoded SHA-256 digest. Short enough to embed cheaply, and tied to
+/// content rather than modification time or crate version, so unrelated rebuilds
+/// don't invalidate caches for files that didn't actually change.
+fn content_etag(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(data);
+    format!("{:016x}", u64::from_be_bytes(digest[..8].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests_content_etag {
+    use super::*;
+
+    #[test]
+    fn is_deterministic_and_16_hex_chars() {
+        let etag = content_etag(b"hello world");
+        assert_eq!(etag.len(), 16);
+        assert!(etag.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(etag, content_etag(b"hello world"));
+    }
+
+    #[test]
+    fn differs_for_different_content() {
+        assert_ne!(content_etag(b"hello"), content_etag(b"world"));
+    }
+}
+
 fn generate_function_header<F: Write>(f: &mut F, fn_name: &str) -> io::Result<()> {
     writeln!(
         f,
@@ -578,12 +746,77 @@ fn generate_variable_return<F: Write>(f: &mut F, variable_name: &str) -> io::Res
     writeln!(f, "{}", variable_name)
 }
 
+/// Appends a `<fn_name>_resolve` function implementing SPA fallback: a path
+/// not present in `resources` resolves to `index_path` unless it looks like a
+/// file request (i.e. its last segment contains a `.`).
+fn generate_resolve_fn<F: Write>(f: &mut F, fn_name: &str, index_path: &str) -> io::Result<()> {
+    writeln!(
+        f,
+        "#[allow(clippy::unreadable_literal)] pub fn {fn_name}_resolve<'r>(resources: &'r ::std::collections::HashMap<&'static str, ::actix_web_static_files::Resource>, path: &str) -> ::std::option::Option<&'r ::actix_web_static_files::Resource> {{
+    if let Some(resource) = resources.get(path) {{
+        return Some(resource);
+    }}
+    if path.rsplit('/').next().map_or(true, |last| !last.contains('.')) {{
+        return resources.get({index_path:?});
+    }}
+    None
+}}",
+        fn_name = fn_name,
+        index_path = index_path,
+    )
+}
+
+#[cfg(test)]
+mod tests_generate_resolve_fn {
+    use super::*;
+
+    #[test]
+    fn emits_resolve_fn_falling_back_to_index_for_extensionless_paths() {
+        let mut buf: Vec<u8> = Vec::new();
+        generate_resolve_fn(&mut buf, "generate", "index.html").unwrap();
+        let generated = String::from_utf8(buf).unwrap();
+
+        assert!(generated.contains("pub fn generate_resolve<'r>"));
+        assert!(generated.contains("resources.get(path)"));
+        assert!(generated.contains("!last.contains('.')"));
+        assert!(generated.contains("resources.get(\"index.html\")"));
+    }
+}
+
 #[cfg(not(windows))]
 const NPM_CMD: &str = "npm";
 
 #[cfg(windows)]
 const NPM_CMD: &str = "npm.cmd";
 
+/// Runs `program args` in `dir`, re-emitting captured stdout/stderr as
+/// `cargo:warning=` lines and failing on a non-zero exit status.
+///
+/// Shared by [`NpmBuildConfig::execute`] and [`NpmBuild`]'s own command
+/// helpers so both builders surface subprocess output and failures the
+/// same way.
+fn run_build_command(program: &str, args: &[String], dir: &Path) -> io::Result<()> {
+    let output = Command::new(program).args(args).current_dir(dir).output()?;
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        println!("cargo:warning={}", line);
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        println!("cargo:warning={}", line);
+    }
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{} {} exited with {}",
+            program,
+            args.join(" "),
+            output.status
+        )));
+    }
+
+    Ok(())
+}
+
 /// Generate resources with run of `npm install` prior to collecting
 /// resources in `resource_dir`.
 ///
@@ -701,16 +934,12 @@ impl NpmBuild {
         self
     }
 
-    /// Executes `npm install`.
+    /// Executes `npm install`, via the shared [`run_build_command`] helper so
+    /// output is re-emitted as `cargo:warning=` lines like [`NpmBuildConfig`].
     pub fn install(self) -> io::Result<Self> {
-        if let Err(e) = self
-            .command()
-            .stderr(Stdio::null())
-            .stdout(Stdio::null())
-            .arg("install")
-            .current_dir(&self.package_json_dir)
-            .status()
-        {
+        let (program, mut args) = self.program_and_args();
+        args.push("install".to_string());
+        if let Err(e) = run_build_command(&program, &args, &self.package_json_dir) {
             eprintln!("Cannot execute {} install: {:?}", &self.executable, e);
             return Err(e);
         }
@@ -718,17 +947,13 @@ impl NpmBuild {
         Ok(self)
     }
 
-    /// Executes `npm run CMD`.
+    /// Executes `npm run CMD`, via the shared [`run_build_command`] helper so
+    /// output is re-emitted as `cargo:warning=` lines like [`NpmBuildConfig`].
     pub fn run(self, cmd: &str) -> io::Result<Self> {
-        if let Err(e) = self
-            .command()
-            .stderr(Stdio::null())
-            .stdout(Stdio::null())
-            .arg("run")
-            .arg(cmd)
-            .current_dir(&self.package_json_dir)
-            .status()
-        {
+        let (program, mut args) = self.program_and_args();
+        args.push("run".to_string());
+        args.push(cmd.to_string());
+        if let Err(e) = run_build_command(&program, &args, &self.package_json_dir) {
             eprintln!("Cannot execute {} run {}: {:?}", &self.executable, cmd, e);
             return Err(e);
         }
@@ -747,18 +972,17 @@ impl NpmBuild {
         self.into()
     }
 
+    /// Returns the program to execute and its leading arguments (before the
+    /// command-specific ones appended by `install`/`run`), mirroring the
+    /// `cmd /c` wrapping `npm`/custom executables need on Windows.
     #[cfg(not(windows))]
-    fn command(&self) -> Command {
-        Command::new(&self.executable)
+    fn program_and_args(&self) -> (String, Vec<String>) {
+        (self.executable.clone(), Vec::new())
     }
 
     #[cfg(windows)]
-    fn command(&self) -> Command {
-        let mut cmd = Command::new("cmd");
-
-        cmd.arg("/c").arg(&self.executable);
-
-        cmd
+    fn program_and_args(&self) -> (String, Vec<String>) {
+        ("cmd".to_string(), vec!["/c".to_string(), self.executable.clone()])
     }
 }
 
@@ -774,4 +998,216 @@ impl From<NpmBuild> for ResourceDir {
     }
 }
 
-mod sets {}
+/// Splits a directory's resources across multiple generated functions instead
+/// of one, via [`sets::generate_resources_sets`].
+pub mod sets {
+    use super::{
+        collect_resources, generate_function_end, generate_function_header, generate_resource_inserts,
+        generate_uses, generate_variable_header, generate_variable_return, NpmBuildConfig,
+        DEFAULT_VARIABLE_NAME,
+    };
+    use std::{
+        fs::{File, Metadata},
+        io::{self, Write},
+        path::{Path, PathBuf},
+    };
+
+    /// Decides, while resources are collected in directory order, where one
+    /// generated set ends and the next begins.
+    pub trait Splitter {
+        /// Called once per resource, in the same order [`generate_resources_sets`]
+        /// will insert them. Returns `true` to start a new set before this
+        /// resource, `false` to keep accumulating into the current one.
+        fn start_new_set(&mut self, path: &Path, metadata: &Metadata) -> bool;
+    }
+
+    /// Groups resources into sets of at most `count` files each.
+    pub struct SplitByCount {
+        count: usize,
+        current: usize,
+    }
+
+    impl SplitByCount {
+        pub fn new(count: usize) -> Self {
+            Self { count, current: 0 }
+        }
+    }
+
+    impl Splitter for SplitByCount {
+        fn start_new_set(&mut self, _path: &Path, _metadata: &Metadata) -> bool {
+            if self.current >= self.count {
+                self.current = 1;
+                true
+            } else {
+                self.current += 1;
+                false
+            }
+        }
+    }
+
+    /// Groups resources into sets bounded by a cumulative uncompressed-byte
+    /// budget (`max_bytes`) rather than a fixed file count, so generated set
+    /// modules - and the `rustc` units they compile into - stay below a target
+    /// size even when a few large assets dominate the directory.
+    ///
+    /// Always places at least one file per set, even if that single file's size
+    /// already exceeds `max_bytes`.
+    pub struct SplitBySize {
+        max_bytes: u64,
+        current_bytes: u64,
+    }
+
+    impl SplitBySize {
+        pub fn new(max_bytes: u64) -> Self {
+            Self {
+                max_bytes,
+                current_bytes: 0,
+            }
+        }
+    }
+
+    impl Splitter for SplitBySize {
+        fn start_new_set(&mut self, _path: &Path, metadata: &Metadata) -> bool {
+            let size = metadata.len();
+            if self.current_bytes > 0 && self.current_bytes + size > self.max_bytes {
+                self.current_bytes = size;
+                true
+            } else {
+                self.current_bytes += size;
+                false
+            }
+        }
+    }
+
+    /// Like [`generate_resources`](super::generate_resources), but spreads the
+    /// embedded resources across multiple `<fn_name>_<n>()` functions inside a
+    /// `mod <mod_name> { ... }`, with boundaries chosen by `splitter`, plus a
+    /// top-level `<fn_name>()` that calls and merges all of them.
+    ///
+    /// Keeping each set in its own function bounds the amount of generated code
+    /// `rustc` has to recompile per incremental change and the size of any single
+    /// compilation unit.
+    ///
+    /// `npm_build`, if given, is run before the directory is scanned, same as
+    /// [`ResourceDir::with_npm_build`](super::ResourceDir::with_npm_build).
+    pub fn generate_resources_sets<P: AsRef<Path>, G: AsRef<Path>, S: Splitter>(
+        project_dir: P,
+        filter: Option<fn(p: &Path) -> bool>,
+        generated_filename: G,
+        mod_name: &str,
+        fn_name: &str,
+        splitter: &mut S,
+        npm_build: Option<&NpmBuildConfig>,
+    ) -> io::Result<()> {
+        if let Some(npm_build) = npm_build {
+            npm_build.execute()?;
+        }
+
+        let resources = collect_resources(&project_dir, filter)?;
+
+        let mut resource_sets: Vec<Vec<(PathBuf, Metadata)>> = Vec::new();
+        for (path, metadata) in resources {
+            // Always consult the splitter, even for the very first resource, so its
+            // accumulators (file count / byte budget) count that resource too -
+            // only the decision to open the very first set is forced.
+            let start_new_set = splitter.start_new_set(&path, &metadata);
+            if resource_sets.is_empty() || start_new_set {
+                resource_sets.push(Vec::new());
+            }
+            resource_sets.last_mut().unwrap().push((path, metadata));
+        }
+
+        let set_count = resource_sets.len();
+
+        let mut f = File::create(&generated_filename).unwrap();
+
+        writeln!(f, "mod {} {{", mod_name)?;
+        generate_uses(&mut f)?;
+
+        for (index, set) in resource_sets.into_iter().enumerate() {
+            let set_fn = format!("{}_{}", fn_name, index);
+            generate_function_header(&mut f, &set_fn)?;
+            generate_variable_header(&mut f, DEFAULT_VARIABLE_NAME)?;
+            generate_resource_inserts(&mut f, &project_dir, DEFAULT_VARIABLE_NAME, set)?;
+            generate_variable_return(&mut f, DEFAULT_VARIABLE_NAME)?;
+            generate_function_end(&mut f)?;
+        }
+
+        writeln!(f, "}}")?;
+
+        generate_function_header(&mut f, fn_name)?;
+        writeln!(
+            f,
+            "let mut {} = ::std::collections::HashMap::new();",
+            DEFAULT_VARIABLE_NAME
+        )?;
+        for index in 0..set_count {
+            writeln!(
+                f,
+                "{}.extend({}::{}_{}());",
+                DEFAULT_VARIABLE_NAME, mod_name, fn_name, index
+            )?;
+        }
+        generate_variable_return(&mut f, DEFAULT_VARIABLE_NAME)?;
+        generate_function_end(&mut f)?;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests_splitters {
+        use super::*;
+        use std::fs;
+
+        fn metadata_for(dir: &Path, name: &str, size: usize) -> Metadata {
+            let path = dir.join(name);
+            fs::write(&path, vec![0u8; size]).unwrap();
+            fs::metadata(&path).unwrap()
+        }
+
+        #[test]
+        fn split_by_count_counts_first_file_toward_the_first_set() {
+            let dir = std::env::temp_dir().join("awsf_split_by_count_test");
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("f");
+
+            let mut splitter = SplitByCount::new(2);
+            let starts: Vec<bool> = (0..5)
+                .map(|i| {
+                    let metadata = metadata_for(&dir, &format!("f{i}"), 1);
+                    splitter.start_new_set(&path, &metadata)
+                })
+                .collect();
+
+            // Sets of 2: [f0, f1], [f2, f3], [f4] - the first file must count
+            // toward the first set's budget instead of slipping in for free.
+            assert_eq!(starts, vec![false, false, true, false, true]);
+
+            fs::remove_dir_all(&dir).ok();
+        }
+
+        #[test]
+        fn split_by_size_counts_first_file_toward_the_byte_budget() {
+            let dir = std::env::temp_dir().join("awsf_split_by_size_test");
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("f");
+
+            let mut splitter = SplitBySize::new(10);
+            let sizes = [6usize, 6, 3, 3, 3];
+            let starts: Vec<bool> = sizes
+                .iter()
+                .enumerate()
+                .map(|(i, &size)| {
+                    let metadata = metadata_for(&dir, &format!("f{i}"), size);
+                    splitter.start_new_set(&path, &metadata)
+                })
+                .collect();
+
+            // [6], [6, 3], [3, 3] - each set's cumulative size stays <= 10, with the
+            // first file's size already counted against the budget.
+            assert_eq!(starts, vec![false, true, false, true, false]);
+
+            fs::remove_dir_all(&dir).ok();
+        }
+    }
+}
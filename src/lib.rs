@@ -1,7 +1,13 @@
 #![doc(test(no_crate_inject))]
 #![doc = include_str!("../README.md")]
 pub mod deps;
+mod r#impl;
 mod resource_files;
+pub use r#impl::{
+    generate_resources, generate_resources_mapping, new_resource, new_resource_with_compression,
+    npm_resource_dir, resource_dir, sets, Compression, Encoding, NpmBuild, NpmBuildConfig,
+    Resource, ResourceDir,
+};
 pub use resource_files::{ResourceFile, ResourceFiles, ResourceFilesCollection, UriSegmentError};
 #[cfg(feature = "builtin-03")]
 pub use static_files_03::*;
@@ -1,3 +1,4 @@
+use actix_service::boxed::{self, BoxService, BoxServiceFactory};
 use actix_web::{
     dev::{
         always_ready, AppService, HttpServiceFactory, ResourceDef, Service, ServiceFactory,
@@ -6,15 +7,31 @@ use actix_web::{
     error::Error,
     guard::{Guard, GuardContext},
     http::{
-        header::{self, ContentType},
+        header::{self, ByteRangeSpec, ContentType, DispositionParam, DispositionType, HeaderValue},
         Method, StatusCode,
     },
     HttpMessage, HttpRequest, HttpResponse, ResponseError,
 };
+use crate::r#impl::Resource;
 use derive_more::{Deref, Display, Error};
-use futures_util::future::{ok, FutureExt, LocalBoxFuture, Ready};
-use static_files::Resource;
-use std::{collections::HashMap, ops::Deref, rc::Rc};
+use futures_util::future::{ok, FutureExt, LocalBoxFuture};
+use std::{borrow::Cow, collections::HashMap, ops::Deref, rc::Rc};
+
+type DefaultServiceFactory =
+    BoxServiceFactory<(), ServiceRequest, ServiceResponse, Error, ()>;
+type DefaultService = BoxService<ServiceRequest, ServiceResponse, Error>;
+
+/// Callback that rewrites a resource's build-time-guessed `Content-Type`, given
+/// the request path and the guessed MIME type; see [`ResourceFiles::mime_override`].
+type MimeOverride = dyn Fn(&str, &mime::Mime) -> Cow<'static, str>;
+
+/// Callback that chooses the `Content-Disposition` for a resource, given the
+/// request path and its MIME type; see [`ResourceFiles::content_disposition`].
+type ContentDispositionFn = dyn Fn(&str, &str) -> DispositionType;
+
+/// Callback that chooses a per-resource `Cache-Control` value; see
+/// [`ResourceFiles::cache_control_fn`].
+type CacheControlFn = dyn Fn(&str, &Resource) -> Option<HeaderValue>;
 
 /// Static resource files handling
 ///
@@ -28,11 +45,11 @@ use std::{collections::HashMap, ops::Deref, rc::Rc};
 /// fn main() {
 ///     // serve root directory with default options:
 ///     // - resolve index.html
-///     let files: HashMap<&'static str, static_files::Resource> = HashMap::new();
+///     let files: HashMap<&'static str, actix_web_static_files::Resource> = HashMap::new();
 ///     let app = App::new()
 ///         .service(actix_web_static_files::ResourceFiles::new("/", files));
 ///     // or subpath with additional option to not resolve index.html
-///     let files: HashMap<&'static str, static_files::Resource> = HashMap::new();
+///     let files: HashMap<&'static str, actix_web_static_files::Resource> = HashMap::new();
 ///     let app = App::new()
 ///         .service(actix_web_static_files::ResourceFiles::new("/imgs", files)
 ///             .do_not_resolve_defaults());
@@ -43,9 +60,29 @@ pub struct ResourceFiles {
     not_resolve_defaults: bool,
     use_guard: bool,
     not_found_resolves_to: Option<String>,
+    prefer_precompressed: bool,
+    cache_control: Option<CacheControl>,
+    mime_override: Option<Rc<MimeOverride>>,
+    default_handler: Option<Rc<DefaultServiceFactory>>,
+    show_listing: bool,
+    directory_renderer: Option<Rc<DirectoryRenderer>>,
+    content_disposition: Option<Rc<ContentDispositionFn>>,
+    guards: Vec<Box<dyn Guard>>,
     inner: Rc<ResourceFilesInner>,
 }
 
+/// Renders a directory listing given the request prefix and the sorted list of
+/// immediate child entries (files and synthetic sub-directories) under it.
+pub type DirectoryRenderer = dyn Fn(&str, &[&str]) -> HttpResponse;
+
+/// Per-resource `Cache-Control` policy, chosen with [`ResourceFiles::cache_control`],
+/// [`ResourceFiles::immutable`] or [`ResourceFiles::cache_control_fn`].
+#[derive(Clone)]
+enum CacheControl {
+    Fixed(HeaderValue),
+    Dynamic(Rc<CacheControlFn>),
+}
+
 pub struct ResourceFilesInner {
     path: String,
     files: HashMap<&'static str, Resource>,
@@ -65,6 +102,14 @@ impl ResourceFiles {
             not_resolve_defaults: false,
             not_found_resolves_to: None,
             use_guard: false,
+            prefer_precompressed: false,
+            cache_control: None,
+            mime_override: None,
+            default_handler: None,
+            show_listing: false,
+            directory_renderer: None,
+            content_disposition: None,
+            guards: Vec::new(),
         }
     }
 
@@ -104,6 +149,137 @@ impl ResourceFiles {
         self
     }
 
+    /// Serve a precompressed variant of a resource when one is available and the
+    /// request's `Accept-Encoding` allows it: either a variant embedded directly
+    /// on the resource (`Resource::compressed`) or a precompressed sibling
+    /// (`foo.js.br`, `foo.js.gz`, `foo.js.zst`) in the embedded set.
+    ///
+    /// Disabled by default, since not every embedded set ships compressed variants.
+    #[must_use]
+    pub fn prefer_precompressed(mut self) -> Self {
+        self.prefer_precompressed = true;
+        self
+    }
+
+    /// Sets a fixed `Cache-Control` header value for every served resource.
+    #[must_use]
+    pub fn cache_control(mut self, value: impl Into<String>) -> Self {
+        let value = HeaderValue::from_str(&value.into()).expect("valid Cache-Control value");
+        self.cache_control = Some(CacheControl::Fixed(value));
+        self
+    }
+
+    /// Convenience wrapper around [`Self::cache_control`] for fingerprinted assets that
+    /// can be cached forever: `Cache-Control: public, max-age=<max_age>, immutable`.
+    #[must_use]
+    pub fn immutable(self, max_age: u32) -> Self {
+        self.cache_control(format!("public, max-age={max_age}, immutable"))
+    }
+
+    /// Chooses `Cache-Control` per resource, e.g. to keep `index.html` as `no-cache`
+    /// while hashed bundles are `immutable`. Returning `None` omits the header.
+    #[must_use]
+    pub fn cache_control_fn(
+        mut self,
+        f: impl Fn(&str, &Resource) -> Option<HeaderValue> + 'static,
+    ) -> Self {
+        self.cache_control = Some(CacheControl::Dynamic(Rc::new(f)));
+        self
+    }
+
+    /// Intercepts the `Content-Type` before it is written to the response, so callers
+    /// can e.g. append `; charset=utf-8`, force `application/wasm`, or serve `.js` as
+    /// `text/javascript` without regenerating the embedded resource table.
+    #[must_use]
+    pub fn mime_override(
+        mut self,
+        f: impl Fn(&str, &mime::Mime) -> Cow<'static, str> + 'static,
+    ) -> Self {
+        self.mime_override = Some(Rc::new(f));
+        self
+    }
+
+    /// Convenience variant of [`Self::mime_override`] for callbacks that only care
+    /// about the guessed top-level MIME name and the request path, and hand back an
+    /// owned `String` rather than a `Cow` (e.g. forcing `charset=utf-8` on `text/*`
+    /// or serving `.wasm` as `application/wasm`).
+    #[must_use]
+    pub fn mime_override_by_name(
+        self,
+        f: impl Fn(&mime::Name<'_>, &str) -> String + 'static,
+    ) -> Self {
+        self.mime_override(move |path, mime| Cow::Owned(f(&mime.type_(), path)))
+    }
+
+    /// Forwards requests with no matching resource (and no SPA fallback configured
+    /// via [`Self::resolve_not_found_to`]) to `svc` instead of returning a hardcoded
+    /// 404. Mirrors actix-files' `default` handler, letting embedded assets mix with
+    /// a dynamic 404 page or a proxy for absent paths.
+    #[must_use]
+    pub fn default_handler<F>(mut self, svc: F) -> Self
+    where
+        F: ServiceFactory<
+                ServiceRequest,
+                Config = (),
+                Response = ServiceResponse,
+                Error = Error,
+                InitError = (),
+            > + 'static,
+    {
+        self.default_handler = Some(Rc::new(boxed::factory(svc)));
+        self
+    }
+
+    /// Enables directory browsing: a request for a "directory" prefix (empty or
+    /// trailing-slash path) with no `index.html` renders an HTML index of the
+    /// embedded keys sharing that prefix instead of a 404.
+    #[must_use]
+    pub fn show_listing(mut self) -> Self {
+        self.show_listing = true;
+        self
+    }
+
+    /// Alias for [`Self::show_listing`].
+    #[must_use]
+    pub fn show_files_listing(self) -> Self {
+        self.show_listing()
+    }
+
+    /// Like [`Self::show_listing`], but with a custom [`DirectoryRenderer`] instead
+    /// of the built-in HTML index.
+    #[must_use]
+    pub fn files_listing_renderer(
+        mut self,
+        f: impl Fn(&str, &[&str]) -> HttpResponse + 'static,
+    ) -> Self {
+        self.show_listing = true;
+        self.directory_renderer = Some(Rc::new(f));
+        self
+    }
+
+    /// Chooses how each resource is presented to the browser via `Content-Disposition`,
+    /// e.g. forcing embedded `.csv`/`.zip`/binary assets to download (`attachment`)
+    /// rather than render inline. The predicate receives the resource path and mime
+    /// type and defaults to [`DispositionType::Inline`] for resources it doesn't
+    /// care about; with no predicate configured at all, no header is sent.
+    #[must_use]
+    pub fn content_disposition(
+        mut self,
+        f: impl Fn(&str, &str) -> DispositionType + 'static,
+    ) -> Self {
+        self.content_disposition = Some(Rc::new(f));
+        self
+    }
+
+    /// Adds a request guard (e.g. host or header match) that must match for this
+    /// mount to handle a request at all, mirroring actix-files' `Files::guard`.
+    /// Can be called multiple times; all accumulated guards must match.
+    #[must_use]
+    pub fn guard(mut self, guard: impl Guard + 'static) -> Self {
+        self.guards.push(Box::new(guard));
+        self
+    }
+
     fn select_guard(&self) -> Box<dyn Guard> {
         if self.not_resolve_defaults {
             Box::new(NotResolveDefaultsGuard::from(self))
@@ -166,18 +342,18 @@ impl From<&ResourceFiles> for ResolveDefaultsGuard {
 }
 
 impl HttpServiceFactory for ResourceFiles {
-    fn register(self, config: &mut AppService) {
+    fn register(mut self, config: &mut AppService) {
         let prefix = self.path.trim_start_matches('/');
         let rdef = if config.is_root() {
             ResourceDef::root_prefix(prefix)
         } else {
             ResourceDef::prefix(prefix)
         };
-        let guards = if self.use_guard && self.not_found_resolves_to.is_none() {
-            Some(vec![self.select_guard()])
-        } else {
-            None
-        };
+        let mut guards = std::mem::take(&mut self.guards);
+        if self.use_guard && self.not_found_resolves_to.is_none() {
+            guards.push(self.select_guard());
+        }
+        let guards = if guards.is_empty() { None } else { Some(guards) };
         config.register_service(rdef, guards, self, None);
     }
 }
@@ -191,11 +367,36 @@ impl ServiceFactory<ServiceRequest> for ResourceFiles {
     type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
 
     fn new_service(&self, _: ()) -> Self::Future {
-        ok(ResourceFilesService {
-            resolve_defaults: !self.not_resolve_defaults,
-            not_found_resolves_to: self.not_found_resolves_to.clone(),
-            inner: self.inner.clone(),
-        })
+        let resolve_defaults = !self.not_resolve_defaults;
+        let not_found_resolves_to = self.not_found_resolves_to.clone();
+        let prefer_precompressed = self.prefer_precompressed;
+        let cache_control = self.cache_control.clone();
+        let mime_override = self.mime_override.clone();
+        let inner = self.inner.clone();
+        let default_handler = self.default_handler.clone();
+        let show_listing = self.show_listing;
+        let directory_renderer = self.directory_renderer.clone();
+        let content_disposition = self.content_disposition.clone();
+
+        async move {
+            let default_handler = match default_handler {
+                Some(f) => Some(Rc::new(f.new_service(()).await?)),
+                None => None,
+            };
+
+            Ok(ResourceFilesService {
+                resolve_defaults,
+                not_found_resolves_to,
+                prefer_precompressed,
+                cache_control,
+                mime_override,
+                default_handler,
+                show_listing,
+                directory_renderer,
+                content_disposition,
+                inner,
+            })
+        }
         .boxed_local()
     }
 }
@@ -204,6 +405,13 @@ impl ServiceFactory<ServiceRequest> for ResourceFiles {
 pub struct ResourceFilesService {
     resolve_defaults: bool,
     not_found_resolves_to: Option<String>,
+    prefer_precompressed: bool,
+    cache_control: Option<CacheControl>,
+    mime_override: Option<Rc<MimeOverride>>,
+    default_handler: Option<Rc<DefaultService>>,
+    show_listing: bool,
+    directory_renderer: Option<Rc<DirectoryRenderer>>,
+    content_disposition: Option<Rc<ContentDispositionFn>>,
     #[deref]
     inner: Rc<ResourceFilesInner>,
 }
@@ -211,7 +419,7 @@ pub struct ResourceFilesService {
 impl Service<ServiceRequest> for ResourceFilesService {
     type Response = ServiceResponse;
     type Error = Error;
-    type Future = Ready<Result<Self::Response, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     always_ready!();
 
@@ -225,79 +433,447 @@ impl Service<ServiceRequest> for ResourceFilesService {
                         .insert_header(ContentType::plaintext())
                         .insert_header((header::ALLOW, "GET, HEAD"))
                         .body("This resource only supports GET and HEAD."),
-                ));
+                ))
+                .boxed_local();
             }
         }
 
-        let req_path = req.match_info().unprocessed();
-        let mut item = self.files.get(req_path);
+        let req_path = req.match_info().unprocessed().to_string();
+        let mut item = self.files.get(req_path.as_str());
+        let mut item_path = req_path.clone();
 
         if item.is_none()
             && self.resolve_defaults
             && (req_path.is_empty() || req_path.ends_with('/'))
         {
-            let index_req_path = req_path.to_string() + INDEX_HTML;
+            let index_req_path = req_path.clone() + INDEX_HTML;
             item = self.files.get(index_req_path.trim_start_matches('/'));
+            item_path = index_req_path.trim_start_matches('/').to_string();
         }
 
-        let (req, response) = if item.is_some() {
-            let (req, _) = req.into_parts();
-            let response = respond_to(&req, item);
-            (req, response)
-        } else {
-            let real_path = match get_pathbuf(req_path) {
-                Ok(item) => item,
-                Err(e) => return ok(req.error_response(e)),
-            };
+        if item.is_some() {
+            let (httpreq, _) = req.into_parts();
+            let response = self.respond_to(&httpreq, item, &item_path);
+            return ok(ServiceResponse::new(httpreq, response)).boxed_local();
+        }
+
+        if self.show_listing && (req_path.is_empty() || req_path.ends_with('/')) {
+            let listing_prefix = req_path.trim_start_matches('/');
+            let entries = directory_entries(&self.files, listing_prefix);
+            // An empty listing means the prefix doesn't correspond to any embedded
+            // resource at all, so fall through to the usual not-found handling
+            // instead of rendering a directory listing with nothing in it.
+            if !entries.is_empty() {
+                let response = self.render_listing(listing_prefix, &entries);
+                let (httpreq, _) = req.into_parts();
+                return ok(ServiceResponse::new(httpreq, response)).boxed_local();
+            }
+        }
 
-            let (req, _) = req.into_parts();
+        let real_path = match get_pathbuf(&req_path) {
+            Ok(p) => p,
+            Err(e) => return ok(req.error_response(e)).boxed_local(),
+        };
+
+        let mut item = self.files.get(real_path.as_str());
+        let mut item_path = real_path.clone();
 
-            let mut item = self.files.get(real_path.as_str());
+        if item.is_none() && self.not_found_resolves_to.is_some() {
+            let not_found_path = self.not_found_resolves_to.as_ref().unwrap();
+            item = self.files.get(not_found_path.as_str());
+            item_path = not_found_path.clone();
+        }
 
-            if item.is_none() && self.not_found_resolves_to.is_some() {
-                let not_found_path = self.not_found_resolves_to.as_ref().unwrap();
-                item = self.files.get(not_found_path.as_str());
+        if item.is_none() {
+            if let Some(default_handler) = self.default_handler.clone() {
+                return async move { default_handler.call(req).await }.boxed_local();
             }
+        }
 
-            let response = respond_to(&req, item);
-            (req, response)
-        };
+        let (httpreq, _) = req.into_parts();
+        let response = self.respond_to(&httpreq, item, &item_path);
+        ok(ServiceResponse::new(httpreq, response)).boxed_local()
+    }
+}
+
+impl ResourceFilesService {
+    fn render_listing(&self, prefix: &str, entries: &[&str]) -> HttpResponse {
+        match &self.directory_renderer {
+            Some(renderer) => renderer(prefix, entries),
+            None => default_directory_listing(prefix, entries),
+        }
+    }
+
+    fn respond_to(&self, req: &HttpRequest, item: Option<&Resource>, item_path: &str) -> HttpResponse {
+        let precompressed = item
+            .filter(|_| self.prefer_precompressed)
+            .and_then(|file| find_precompressed(file, &self.files, item_path, req));
+
+        let cache_control = item.and_then(|file| match self.cache_control.as_ref()? {
+            CacheControl::Fixed(value) => Some(value.clone()),
+            CacheControl::Dynamic(f) => f(item_path, file),
+        });
+
+        let content_type = item.map(|file| resolve_content_type(file, item_path, self.mime_override.as_deref()));
+
+        let content_disposition = item.and_then(|file| {
+            let f = self.content_disposition.as_ref()?;
+            let filename = item_path.rsplit('/').next().unwrap_or(item_path).to_string();
+            Some(header::ContentDisposition {
+                disposition: f(item_path, file.mime_type),
+                parameters: vec![DispositionParam::Filename(filename)],
+            })
+        });
+
+        respond_to(
+            req,
+            item,
+            precompressed,
+            cache_control,
+            content_type,
+            content_disposition,
+        )
+    }
+}
+
+/// Resolves the `Content-Type` for `file`, consulting the user-supplied
+/// `mime_override` hook (if any) before falling back to the build-time guess.
+fn resolve_content_type(
+    file: &Resource,
+    path: &str,
+    mime_override: Option<&MimeOverride>,
+) -> Cow<'static, str> {
+    match mime_override {
+        Some(f) => match file.mime_type.parse::<mime::Mime>() {
+            Ok(mime) => f(path, &mime),
+            Err(_) => Cow::Borrowed(file.mime_type),
+        },
+        None => Cow::Borrowed(file.mime_type),
+    }
+}
+
+const PRECOMPRESSED_ENCODINGS: &[(&str, &str)] =
+    &[(".br", "br"), (".zst", "zstd"), (".gz", "gzip")];
+
+/// Picks the best precompressed variant of `item` the client accepts.
+///
+/// Checks variants embedded directly on the resource (via [`Resource::compressed`],
+/// already ordered best-first) before falling back to precompressed sibling
+/// resources in the embedded set, e.g. `foo.js.br` for `foo.js`.
+fn find_precompressed<'a>(
+    item: &'a Resource,
+    files: &'a HashMap<&'static str, Resource>,
+    path: &str,
+    req: &HttpRequest,
+) -> Option<(&'static [u8], &'static str)> {
+    let accept_encoding = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    for (encoding, data) in item.compressed {
+        if accepts_encoding(accept_encoding, encoding.as_str()) {
+            return Some((data, encoding.as_str()));
+        }
+    }
+
+    PRECOMPRESSED_ENCODINGS.iter().find_map(|(suffix, name)| {
+        if !accepts_encoding(accept_encoding, name) {
+            return None;
+        }
+        let sibling_key = format!("{path}{suffix}");
+        files.get(sibling_key.as_str()).map(|res| (res.data, *name))
+    })
+}
+
+/// Minimal `Accept-Encoding` match: looks for `coding` or `*` among the comma
+/// separated list, excluding entries whose `q` parameter is exactly `0`.
+fn accepts_encoding(accept_encoding: &str, coding: &str) -> bool {
+    accept_encoding.split(',').any(|part| {
+        let mut segments = part.split(';');
+        let name = segments.next().unwrap_or("").trim();
+        if name != "*" && !name.eq_ignore_ascii_case(coding) {
+            return false;
+        }
+        let q = segments
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .and_then(|value| value.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        q != 0.0
+    })
+}
+
+/// Collects the sorted, de-duplicated immediate children of `prefix` among the
+/// embedded keys, collapsing nested files into a single `sub/` directory entry.
+fn directory_entries<'a>(files: &'a HashMap<&'static str, Resource>, prefix: &str) -> Vec<&'a str> {
+    let mut entries: Vec<&str> = files
+        .keys()
+        .filter_map(|key| key.strip_prefix(prefix))
+        .filter(|rest| !rest.is_empty())
+        .map(|rest| match rest.find('/') {
+            Some(idx) => &rest[..=idx],
+            None => rest,
+        })
+        .collect();
+
+    entries.sort_unstable();
+    entries.dedup();
+    entries
+}
+
+fn default_directory_listing(prefix: &str, entries: &[&str]) -> HttpResponse {
+    let mut body = String::new();
+    body.push_str("<!DOCTYPE html><html><head><title>Index of ");
+    body.push_str(&escape_html(prefix));
+    body.push_str("</title></head><body><h1>Index of ");
+    body.push_str(&escape_html(prefix));
+    body.push_str("</h1><ul>");
 
-        ok(ServiceResponse::new(req, response))
+    if !prefix.is_empty() {
+        body.push_str("<li><a href=\"../\">../</a></li>");
     }
+
+    for entry in entries {
+        let escaped = escape_html(entry);
+        body.push_str(&format!("<li><a href=\"{escaped}\">{escaped}</a></li>"));
+    }
+
+    body.push_str("</ul></body></html>");
+
+    HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/html; charset=utf-8"))
+        .body(body)
 }
 
-fn respond_to(req: &HttpRequest, item: Option<&Resource>) -> HttpResponse {
+fn escape_html(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#39;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn respond_to(
+    req: &HttpRequest,
+    item: Option<&Resource>,
+    precompressed: Option<(&'static [u8], &'static str)>,
+    cache_control: Option<HeaderValue>,
+    content_type: Option<Cow<'static, str>>,
+    content_disposition: Option<header::ContentDisposition>,
+) -> HttpResponse {
     if let Some(file) = item {
-        let etag = Some(header::EntityTag::new_strong(format!(
-            "{:x}:{:x}",
-            file.data.len(),
-            file.modified
-        )));
+        // The ETag is the build-time content hash of the logical (uncompressed)
+        // resource, so conditional requests stay stable across encodings and only
+        // change when the file's actual content does.
+        let etag = Some(header::EntityTag::new_strong(file.etag.to_string()));
+
+        let last_modified = header::HttpDate::from(
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(file.modified),
+        );
 
-        let precondition_failed = !any_match(etag.as_ref(), req);
+        // ETag preconditions take precedence over the date-based ones per RFC 7232.
+        let precondition_failed =
+            !any_match(etag.as_ref(), req) || unmodified_since_failed(last_modified, req);
 
-        let not_modified = !none_match(etag.as_ref(), req);
+        let not_modified =
+            !none_match(etag.as_ref(), req) || not_modified_since(last_modified, req);
 
         let mut resp = HttpResponse::build(StatusCode::OK);
-        resp.insert_header((header::CONTENT_TYPE, file.mime_type));
+        resp.insert_header(header::LastModified(last_modified));
 
-        if let Some(etag) = etag {
+        if let Some(etag) = etag.clone() {
             resp.insert_header(header::ETag(etag));
         }
 
+        if let Some(cache_control) = cache_control {
+            resp.insert_header((header::CACHE_CONTROL, cache_control));
+        }
+
+        // Per RFC 7232 §4.1, 304/412 responses only need to repeat the validators
+        // above (ETag, Last-Modified, Cache-Control); entity headers like
+        // Content-Type/Accept-Ranges describe a body that won't be sent.
         if precondition_failed {
             return resp.status(StatusCode::PRECONDITION_FAILED).finish();
         } else if not_modified {
             return resp.status(StatusCode::NOT_MODIFIED).finish();
         }
 
-        resp.body(file.data)
+        let content_type = content_type
+            .unwrap_or(Cow::Borrowed(file.mime_type))
+            .into_owned();
+        resp.insert_header((header::CONTENT_TYPE, content_type.clone()));
+        resp.insert_header((header::ACCEPT_RANGES, "bytes"));
+
+        if let Some(content_disposition) = content_disposition {
+            resp.insert_header(content_disposition);
+        }
+
+        let body = if let Some((encoded, encoding)) = precompressed {
+            resp.insert_header((header::CONTENT_ENCODING, encoding));
+            resp.insert_header((header::VARY, "Accept-Encoding"));
+            encoded
+        } else {
+            file.data
+        };
+
+        let length = body.len() as u64;
+
+        // A `Range` is a byte offset into the representation actually sent. For a
+        // precompressed body that's the compressed stream, which (unlike the plain
+        // file) can't be sliced at an arbitrary offset and still decode - so ranges
+        // are only honored against the uncompressed identity encoding.
+        if precompressed.is_none() {
+            if let Some(range) = req.get_header::<header::Range>() {
+                if range_satisfies_if_range(etag.as_ref(), last_modified, req) {
+                    return respond_with_range(resp, range, body, length, &content_type);
+                }
+            }
+        }
+
+        resp.body(body)
     } else {
         HttpResponse::NotFound().body("Not found")
     }
 }
 
+/// Returns true if there is no `If-Range` header, or if it matches the current `etag`.
+///
+/// When `If-Range` is present but stale, the `Range` header must be ignored and the
+/// full (200) response returned instead.
+fn range_satisfies_if_range(
+    etag: Option<&header::EntityTag>,
+    last_modified: header::HttpDate,
+    req: &HttpRequest,
+) -> bool {
+    match req.get_header::<header::IfRange>() {
+        None => true,
+        Some(header::IfRange::EntityTag(if_range_etag)) => {
+            matches!(etag, Some(etag) if etag.strong_eq(&if_range_etag))
+        }
+        Some(header::IfRange::Date(since)) => last_modified <= since,
+    }
+}
+
+/// Single supported range, after resolving `start-`, `-suffix` and `start-end` forms
+/// against the resource length.
+#[derive(Debug, PartialEq)]
+struct ResolvedRange {
+    start: u64,
+    end: u64,
+}
+
+fn respond_with_range(
+    mut resp: actix_web::HttpResponseBuilder,
+    range: header::Range,
+    data: &'static [u8],
+    length: u64,
+    mime_type: &str,
+) -> HttpResponse {
+    let header::Range::Bytes(ranges) = range else {
+        return resp.body(data);
+    };
+
+    let resolved: Vec<ResolvedRange> = ranges
+        .iter()
+        .filter_map(|r| resolve_range(r, length))
+        .collect();
+
+    if resolved.is_empty() {
+        return resp
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .insert_header((header::CONTENT_RANGE, format!("bytes */{}", length)))
+            .finish();
+    }
+
+    if resolved.len() == 1 {
+        let ResolvedRange { start, end } = resolved[0];
+
+        resp.status(StatusCode::PARTIAL_CONTENT);
+        resp.insert_header((
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, length),
+        ));
+        resp.insert_header((header::CONTENT_LENGTH, end - start + 1));
+
+        return resp.body(&data[start as usize..=end as usize]);
+    }
+
+    multipart_byteranges(resp, &resolved, data, length, mime_type)
+}
+
+const MULTIPART_BOUNDARY: &str = "ACTIX_WEB_STATIC_FILES_BOUNDARY";
+
+/// Answers several ranges in a single `multipart/byteranges` body, each part carrying
+/// its own `Content-Type`/`Content-Range` headers.
+fn multipart_byteranges(
+    mut resp: actix_web::HttpResponseBuilder,
+    ranges: &[ResolvedRange],
+    data: &'static [u8],
+    length: u64,
+    mime_type: &str,
+) -> HttpResponse {
+    let mut body = Vec::new();
+
+    for ResolvedRange { start, end } in ranges {
+        body.extend_from_slice(format!("--{MULTIPART_BOUNDARY}\r\n").as_bytes());
+        body.extend_from_slice(format!("Content-Type: {mime_type}\r\n").as_bytes());
+        body.extend_from_slice(
+            format!("Content-Range: bytes {start}-{end}/{length}\r\n\r\n").as_bytes(),
+        );
+        body.extend_from_slice(&data[*start as usize..=*end as usize]);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+
+    resp.status(StatusCode::PARTIAL_CONTENT);
+    resp.insert_header((
+        header::CONTENT_TYPE,
+        format!("multipart/byteranges; boundary={MULTIPART_BOUNDARY}"),
+    ));
+    resp.body(body)
+}
+
+fn resolve_range(spec: &ByteRangeSpec, length: u64) -> Option<ResolvedRange> {
+    match *spec {
+        ByteRangeSpec::FromTo(start, end) => {
+            let end = end.min(length.saturating_sub(1));
+            if start >= length || start > end {
+                None
+            } else {
+                Some(ResolvedRange { start, end })
+            }
+        }
+        ByteRangeSpec::From(start) => {
+            if start >= length {
+                None
+            } else {
+                Some(ResolvedRange {
+                    start,
+                    end: length - 1,
+                })
+            }
+        }
+        ByteRangeSpec::Last(n) => {
+            if n == 0 || length == 0 {
+                None
+            } else {
+                let start = length.saturating_sub(n.min(length));
+                Some(ResolvedRange {
+                    start,
+                    end: length - 1,
+                })
+            }
+        }
+    }
+}
+
 /// Returns true if `req` has no `If-Match` header or one which matches `etag`.
 fn any_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
     match req.get_header::<header::IfMatch>() {
@@ -315,6 +891,30 @@ fn any_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
     }
 }
 
+/// Returns true if `If-Unmodified-Since` is present, no `If-Match` overrides it, and
+/// the resource was modified after the given date.
+fn unmodified_since_failed(last_modified: header::HttpDate, req: &HttpRequest) -> bool {
+    if req.get_header::<header::IfMatch>().is_some() {
+        return false;
+    }
+    match req.get_header::<header::IfUnmodifiedSince>() {
+        Some(header::IfUnmodifiedSince(since)) => last_modified > since,
+        None => false,
+    }
+}
+
+/// Returns true if `If-Modified-Since` is present, no `If-None-Match` overrides it, and
+/// the resource was not modified after the given date.
+fn not_modified_since(last_modified: header::HttpDate, req: &HttpRequest) -> bool {
+    if req.get_header::<header::IfNoneMatch>().is_some() {
+        return false;
+    }
+    match req.get_header::<header::IfModifiedSince>() {
+        Some(header::IfModifiedSince(since)) => last_modified <= since,
+        None => false,
+    }
+}
+
 /// Returns true if `req` doesn't have an `If-None-Match` header matching `req`.
 fn none_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
     match req.get_header::<header::IfNoneMatch>() {
@@ -394,3 +994,748 @@ fn get_pathbuf(path: &str) -> Result<String, UriSegmentError> {
 
     Ok(buf.join("/"))
 }
+
+#[cfg(test)]
+mod tests_common {
+    use super::*;
+
+    /// Shared `Resource` fixture builder for the test modules below, so each
+    /// one only spells out the fields it actually cares about.
+    pub(super) fn test_resource(
+        data: &'static [u8],
+        modified: u64,
+        mime_type: &'static str,
+        etag: &'static str,
+    ) -> Resource {
+        Resource {
+            data,
+            modified,
+            mime_type,
+            compressed: &[],
+            etag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_range {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn resource(data: &'static [u8]) -> Resource {
+        test_resource(data, 1_700_000_000, "text/plain", "etag123")
+    }
+
+    #[test]
+    fn single_range_returns_206_with_content_range_and_length() {
+        let file = resource(b"hello world");
+        let req = TestRequest::get()
+            .insert_header((header::RANGE, "bytes=0-4"))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 0-4/11"
+        );
+        assert_eq!(resp.headers().get(header::CONTENT_LENGTH).unwrap(), "5");
+    }
+
+    #[test]
+    fn unsatisfiable_range_returns_416_with_asterisk_content_range() {
+        let file = resource(b"hello world");
+        let req = TestRequest::get()
+            .insert_header((header::RANGE, "bytes=100-200"))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */11"
+        );
+    }
+
+    #[test]
+    fn resolve_range_rejects_suffix_range_on_empty_resource() {
+        // A 0-byte embedded resource with `Range: bytes=-5` must not panic on the
+        // `length - 1` computation in the `Last` branch.
+        let spec = header::ByteRangeSpec::Last(5);
+        assert_eq!(resolve_range(&spec, 0), None);
+    }
+
+    #[test]
+    fn resolve_range_last_n_clamps_to_full_length() {
+        let spec = header::ByteRangeSpec::Last(100);
+        let resolved = resolve_range(&spec, 11).unwrap();
+        assert_eq!(resolved.start, 0);
+        assert_eq!(resolved.end, 10);
+    }
+
+    #[test]
+    fn multi_range_request_returns_multipart_byteranges() {
+        let file = resource(b"hello world");
+        let req = TestRequest::get()
+            .insert_header((header::RANGE, "bytes=0-2,5-7"))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(content_type.starts_with("multipart/byteranges; boundary="));
+    }
+
+    #[test]
+    fn range_is_ignored_when_serving_a_precompressed_body() {
+        // Slicing a precompressed stream at an arbitrary byte offset would produce
+        // an undecodable partial gzip/brotli body, so a `Range` request against a
+        // precompressed representation must fall back to the full response.
+        let file = resource(b"hello world");
+        let req = TestRequest::get()
+            .insert_header((header::RANGE, "bytes=0-4"))
+            .to_http_request();
+
+        let resp = respond_to(
+            &req,
+            Some(&file),
+            Some((b"gzipped-body", "gzip")),
+            None,
+            None,
+            None,
+        );
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_ENCODING).unwrap(),
+            "gzip"
+        );
+        // `Content-Length` isn't asserted here: actix-web only computes it at
+        // message-encoding time in the running service, not on a builder's `.body()`.
+        assert!(resp.headers().get(header::CONTENT_RANGE).is_none());
+    }
+}
+
+#[cfg(test)]
+mod tests_encoding {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn accepts_encoding_excludes_only_q_zero() {
+        assert!(accepts_encoding("gzip;q=0.9, br;q=0.5", "gzip"));
+        assert!(accepts_encoding("gzip;q=0.9, br;q=0.5", "br"));
+        assert!(!accepts_encoding("gzip;q=0", "gzip"));
+        assert!(!accepts_encoding("gzip;q=0.0", "gzip"));
+    }
+
+    #[test]
+    fn accepts_encoding_wildcard_respects_its_own_q_value() {
+        assert!(!accepts_encoding("*;q=0, br", "gzip"));
+        assert!(accepts_encoding("*;q=0, br", "br"));
+    }
+
+    #[test]
+    fn find_precompressed_prefers_br_over_gzip_sibling() {
+        let mut files: HashMap<&'static str, Resource> = HashMap::new();
+        files.insert(
+            "app.js.br",
+            Resource {
+                data: b"BR",
+                modified: 0,
+                mime_type: "text/javascript",
+                compressed: &[],
+                etag: "e",
+            },
+        );
+        files.insert(
+            "app.js.gz",
+            Resource {
+                data: b"GZ",
+                modified: 0,
+                mime_type: "text/javascript",
+                compressed: &[],
+                etag: "e",
+            },
+        );
+        let item = Resource {
+            data: b"plain",
+            modified: 0,
+            mime_type: "text/javascript",
+            compressed: &[],
+            etag: "e",
+        };
+        let req = TestRequest::get()
+            .insert_header((header::ACCEPT_ENCODING, "gzip, br"))
+            .to_http_request();
+
+        let (data, encoding) = find_precompressed(&item, &files, "app.js", &req).unwrap();
+
+        assert_eq!(encoding, "br");
+        assert_eq!(data, b"BR");
+    }
+
+    #[test]
+    fn find_precompressed_prefers_embedded_variant_over_sibling_file() {
+        use crate::r#impl::Encoding;
+
+        // A sibling resource in the embedded set is present too, but the variant embedded directly
+        // on the resource should win.
+        let mut files: HashMap<&'static str, Resource> = HashMap::new();
+        files.insert(
+            "app.js.gz",
+            Resource {
+                data: b"SIBLING-GZ",
+                modified: 0,
+                mime_type: "text/javascript",
+                compressed: &[],
+                etag: "e",
+            },
+        );
+        let item = Resource {
+            data: b"plain",
+            modified: 0,
+            mime_type: "text/javascript",
+            compressed: &[(Encoding::Gzip, b"EMBEDDED-GZ")],
+            etag: "e",
+        };
+        let req = TestRequest::get()
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_http_request();
+
+        let (data, encoding) = find_precompressed(&item, &files, "app.js", &req).unwrap();
+
+        assert_eq!(encoding, "gzip");
+        assert_eq!(data, b"EMBEDDED-GZ");
+    }
+}
+
+#[cfg(test)]
+mod tests_preconditions {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn resource() -> Resource {
+        test_resource(b"hello world", 1_700_000_000, "text/plain", "etag123")
+    }
+
+    #[test]
+    fn if_none_match_hit_returns_304_without_entity_headers() {
+        let file = resource();
+        let req = TestRequest::get()
+            .insert_header((header::IF_NONE_MATCH, "\"etag123\""))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert!(resp.headers().get(header::ETAG).is_some());
+        assert!(resp.headers().get(header::LAST_MODIFIED).is_some());
+        assert!(resp.headers().get(header::CONTENT_TYPE).is_none());
+        assert!(resp.headers().get(header::ACCEPT_RANGES).is_none());
+    }
+
+    #[test]
+    fn if_match_miss_returns_412_without_entity_headers() {
+        let file = resource();
+        let req = TestRequest::get()
+            .insert_header((header::IF_MATCH, "\"not-the-etag\""))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+        assert!(resp.headers().get(header::ETAG).is_some());
+        assert!(resp.headers().get(header::CONTENT_TYPE).is_none());
+        assert!(resp.headers().get(header::ACCEPT_RANGES).is_none());
+    }
+
+    #[test]
+    fn etag_precondition_takes_precedence_over_date_based() {
+        // If-Match fails (etag precondition) while If-Unmodified-Since would
+        // otherwise pass; RFC 7232 says the etag-based check wins.
+        let file = resource();
+        let req = TestRequest::get()
+            .insert_header((header::IF_MATCH, "\"not-the-etag\""))
+            .insert_header((
+                header::IF_UNMODIFIED_SINCE,
+                "Thu, 01 Jan 2099 00:00:00 GMT",
+            ))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+    }
+}
+
+#[cfg(test)]
+mod tests_last_modified {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn resource() -> Resource {
+        test_resource(b"hello world", 1_700_000_000, "text/plain", "etag123")
+    }
+
+    #[test]
+    fn emits_last_modified_from_build_time_mtime() {
+        let file = resource();
+        let req = TestRequest::get().to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        let last_modified = header::HttpDate::from(
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(file.modified),
+        );
+        assert_eq!(
+            resp.headers().get(header::LAST_MODIFIED).unwrap(),
+            last_modified.to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn if_modified_since_future_date_returns_304() {
+        let file = resource();
+        let req = TestRequest::get()
+            .insert_header((header::IF_MODIFIED_SINCE, "Thu, 01 Jan 2099 00:00:00 GMT"))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[test]
+    fn if_range_with_stale_etag_ignores_range_and_returns_full_200() {
+        let file = resource();
+        let req = TestRequest::get()
+            .insert_header((header::RANGE, "bytes=0-4"))
+            .insert_header((header::IF_RANGE, "\"stale-etag\""))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn if_range_with_matching_etag_honors_range() {
+        let file = resource();
+        let req = TestRequest::get()
+            .insert_header((header::RANGE, "bytes=0-4"))
+            .insert_header((header::IF_RANGE, "\"etag123\""))
+            .to_http_request();
+
+        let resp = respond_to(&req, Some(&file), None, None, None, None);
+
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+    }
+}
+
+#[cfg(test)]
+mod tests_cache_control {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+
+    fn resource() -> Resource {
+        test_resource(b"hi", 0, "text/plain", "e")
+    }
+
+    fn files_with(path: &'static str) -> HashMap<&'static str, Resource> {
+        let mut files = HashMap::new();
+        files.insert(path, resource());
+        files
+    }
+
+    #[actix_web::test]
+    async fn immutable_sets_fixed_cache_control_header() {
+        let files = files_with("a.txt");
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).immutable(31536000),
+        ))
+        .await;
+
+        let req = TestRequest::get().uri("/a.txt").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=31536000, immutable"
+        );
+    }
+
+    #[actix_web::test]
+    async fn cache_control_fn_can_omit_header_per_resource() {
+        let files = files_with("index.html");
+        let app = init_service(App::new().service(ResourceFiles::new("/", files).cache_control_fn(
+            |path, _| {
+                if path == "index.html" {
+                    None
+                } else {
+                    Some(HeaderValue::from_static("public"))
+                }
+            },
+        )))
+        .await;
+
+        let req = TestRequest::get().uri("/index.html").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+    }
+}
+
+#[cfg(test)]
+mod tests_mime_override {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+
+    fn resource(mime_type: &'static str) -> Resource {
+        test_resource(b"{}", 0, mime_type, "e")
+    }
+
+    #[actix_web::test]
+    async fn mime_override_rewrites_content_type() {
+        let mut files = HashMap::new();
+        files.insert("data.json", resource("application/json"));
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).mime_override(|_, mime| {
+                Cow::Owned(format!("{mime}; charset=utf-8"))
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::get().uri("/data.json").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json; charset=utf-8"
+        );
+    }
+
+    #[actix_web::test]
+    async fn mime_override_by_name_forces_wasm_content_type() {
+        let mut files = HashMap::new();
+        files.insert("app.wasm", resource("application/octet-stream"));
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).mime_override_by_name(|name, path| {
+                if path.ends_with(".wasm") {
+                    "application/wasm".to_string()
+                } else {
+                    name.as_ref().to_string()
+                }
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::get().uri("/app.wasm").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/wasm"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_content_disposition {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::App;
+
+    fn resource() -> Resource {
+        test_resource(b"binary", 0, "application/octet-stream", "e")
+    }
+
+    #[actix_web::test]
+    async fn forces_attachment_for_matching_resources() {
+        let mut files = HashMap::new();
+        files.insert("report.csv", resource());
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).content_disposition(|path, _| {
+                if path.ends_with(".csv") {
+                    DispositionType::Attachment
+                } else {
+                    DispositionType::Inline
+                }
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::get().uri("/report.csv").to_request();
+        let resp = call_service(&app, req).await;
+
+        let value = resp
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(value.starts_with("attachment"));
+        assert!(value.contains("filename=\"report.csv\""));
+    }
+
+    #[actix_web::test]
+    async fn no_predicate_configured_omits_header() {
+        let mut files = HashMap::new();
+        files.insert("report.csv", resource());
+        let app = init_service(App::new().service(ResourceFiles::new("/", files))).await;
+
+        let req = TestRequest::get().uri("/report.csv").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert!(resp.headers().get(header::CONTENT_DISPOSITION).is_none());
+    }
+}
+
+#[cfg(test)]
+mod tests_empty_listing {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::App;
+
+    fn resource() -> Resource {
+        test_resource(b"hi", 0, "text/plain", "e")
+    }
+
+    #[actix_web::test]
+    async fn non_empty_prefix_renders_listing() {
+        let mut files = HashMap::new();
+        files.insert("docs/readme.txt", resource());
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).show_files_listing(),
+        ))
+        .await;
+
+        let req = TestRequest::get().uri("/docs/").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn unknown_prefix_falls_through_to_not_found_instead_of_empty_listing() {
+        let mut files = HashMap::new();
+        files.insert("docs/readme.txt", resource());
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).show_files_listing(),
+        ))
+        .await;
+
+        let req = TestRequest::get().uri("/nothing-here/").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
+
+#[cfg(test)]
+mod tests_default_handler {
+    use super::*;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    #[actix_web::test]
+    async fn missing_resource_falls_through_to_default_handler() {
+        let files: HashMap<&'static str, Resource> = HashMap::new();
+        let app = init_service(App::new().service(ResourceFiles::new("/", files).default_handler(
+            web::to(|| async { HttpResponse::Ok().body("fallback") }),
+        )))
+        .await;
+
+        let req = TestRequest::get().uri("/missing.txt").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn missing_resource_without_default_handler_returns_404() {
+        let files: HashMap<&'static str, Resource> = HashMap::new();
+        let app = init_service(App::new().service(ResourceFiles::new("/", files))).await;
+
+        let req = TestRequest::get().uri("/missing.txt").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
+
+#[cfg(test)]
+mod tests_directory_listing {
+    use super::*;
+
+    #[test]
+    fn directory_entries_collapses_nested_files_into_sub_dir() {
+        let mut files: HashMap<&'static str, Resource> = HashMap::new();
+        files.insert(
+            "assets/app.js",
+            Resource {
+                data: b"",
+                modified: 0,
+                mime_type: "text/javascript",
+                compressed: &[],
+                etag: "e",
+            },
+        );
+        files.insert(
+            "assets/sub/dir.css",
+            Resource {
+                data: b"",
+                modified: 0,
+                mime_type: "text/css",
+                compressed: &[],
+                etag: "e",
+            },
+        );
+
+        let entries = directory_entries(&files, "assets/");
+
+        assert_eq!(entries, vec!["app.js", "sub/"]);
+    }
+
+    #[test]
+    fn escape_html_escapes_reserved_characters() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_guard {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::guard;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::App;
+
+    fn resource() -> Resource {
+        test_resource(b"hi", 0, "text/plain", "e")
+    }
+
+    #[actix_web::test]
+    async fn non_matching_guard_leaves_request_unhandled() {
+        let mut files = HashMap::new();
+        files.insert("a.txt", resource());
+        let app = init_service(
+            App::new()
+                .service(ResourceFiles::new("/", files).guard(guard::Header("x-api-key", "secret"))),
+        )
+        .await;
+
+        let req = TestRequest::get().uri("/a.txt").to_request();
+        let resp = call_service(&app, req).await;
+
+        // No other service is mounted to handle it, so actix-web's router 404s.
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn matching_guard_serves_the_resource() {
+        let mut files = HashMap::new();
+        files.insert("a.txt", resource());
+        let app = init_service(
+            App::new()
+                .service(ResourceFiles::new("/", files).guard(guard::Header("x-api-key", "secret"))),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/a.txt")
+            .insert_header(("x-api-key", "secret"))
+            .to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
+
+#[cfg(test)]
+mod tests_nested_index {
+    use super::tests_common::test_resource;
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::App;
+
+    fn resource(data: &'static [u8]) -> Resource {
+        test_resource(data, 0, "text/html", "e")
+    }
+
+    #[actix_web::test]
+    async fn nested_directory_index_prefers_its_own_precompressed_sibling() {
+        let mut files = HashMap::new();
+        files.insert("docs/index.html", resource(b"<html>plain</html>"));
+        files.insert("docs/index.html.gz", resource(b"gzipped-docs-index"));
+        // A root-level sibling with the same suffix must not be picked instead -
+        // regression test for item_path being hardcoded to "index.html".
+        files.insert("index.html.gz", resource(b"gzipped-root-index"));
+        let app = init_service(App::new().service(
+            ResourceFiles::new("/", files).prefer_precompressed(),
+        ))
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/docs/")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = actix_web::test::read_body(resp).await;
+        assert_eq!(body, b"gzipped-docs-index".as_ref());
+    }
+
+    #[actix_web::test]
+    async fn nested_directory_index_cache_control_sees_the_resolved_key() {
+        let mut files = HashMap::new();
+        files.insert("docs/index.html", resource(b"<html>plain</html>"));
+        let app = init_service(App::new().service(ResourceFiles::new("/", files).cache_control_fn(
+            |path, _file| {
+                let value = if path == "docs/index.html" {
+                    "no-cache"
+                } else {
+                    "immutable"
+                };
+                Some(HeaderValue::from_static(value))
+            },
+        )))
+        .await;
+
+        let req = TestRequest::get().uri("/docs/").to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+    }
+}